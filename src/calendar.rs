@@ -0,0 +1,227 @@
+// Copyright 2024 Felipe Torres González
+
+//! Trading calendar of the Spanish stock market.
+//!
+//! BME does not trade on weekends nor on the following holidays, which are
+//! computed for any year:
+//! - New Year's Day (January 1st).
+//! - Good Friday and Easter Monday.
+//! - Labour Day (May 1st).
+//! - Christmas Day and St. Stephen's Day (December 25th and 26th).
+//!
+//! Extra closures, announced by BME in its yearly calendar, can be added on
+//! top of the rules.
+
+use crate::error::IbexError;
+use chrono::{Datelike, Days, NaiveDate, Weekday};
+use std::collections::BTreeSet;
+use toml::Table;
+
+/// Calendar of the days in which the Spanish stock market trades.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TradingCalendar {
+    extra_holidays: BTreeSet<NaiveDate>,
+}
+
+impl TradingCalendar {
+    /// Build a calendar that only applies the BME holiday rules.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add extra closures on top of the BME holiday rules.
+    pub fn with_holidays(mut self, holidays: impl IntoIterator<Item = NaiveDate>) -> Self {
+        self.extra_holidays.extend(holidays);
+        self
+    }
+
+    /// Build a calendar from a TOML description of the extra closures.
+    ///
+    /// # Description
+    ///
+    /// The input shall contain an array `holidays` of dates:
+    ///
+    /// ```toml
+    /// holidays = [2024-12-24, 2024-12-31]
+    /// ```
+    ///
+    /// Dates might be written either as TOML dates or as `YYYY-MM-DD` strings.
+    pub fn from_toml_str(data: &str) -> Result<Self, IbexError> {
+        let table = data.parse::<Table>()?;
+        let invalid = |msg: String| IbexError::InvalidCalendar(msg);
+
+        let entries = table
+            .get("holidays")
+            .and_then(|h| h.as_array())
+            .ok_or_else(|| invalid("missing array of holidays".to_string()))?;
+
+        let mut holidays = BTreeSet::new();
+        for entry in entries {
+            let text = match entry {
+                toml::Value::Datetime(date) => date.to_string(),
+                toml::Value::String(date) => date.clone(),
+                other => return Err(invalid(format!("{other} is not a date"))),
+            };
+            let date = NaiveDate::parse_from_str(&text, "%Y-%m-%d")
+                .map_err(|_| invalid(format!("{text:?} is not a date")))?;
+            holidays.insert(date);
+        }
+
+        Ok(TradingCalendar {
+            extra_holidays: holidays,
+        })
+    }
+
+    /// Check whether `date` is a market holiday, either by rule or as an extra closure.
+    pub fn is_holiday(&self, date: NaiveDate) -> bool {
+        if self.extra_holidays.contains(&date) {
+            return true;
+        }
+
+        match (date.month(), date.day()) {
+            (1, 1) | (5, 1) | (12, 25) | (12, 26) => true,
+            _ => easter_sunday(date.year()).is_some_and(|easter| {
+                easter.checked_sub_days(Days::new(2)) == Some(date)
+                    || easter.checked_add_days(Days::new(1)) == Some(date)
+            }),
+        }
+    }
+
+    /// Check whether the market trades on `date`.
+    pub fn is_trading_day(&self, date: NaiveDate) -> bool {
+        !matches!(date.weekday(), Weekday::Sat | Weekday::Sun) && !self.is_holiday(date)
+    }
+
+    /// Get the first trading day strictly after `date`.
+    ///
+    /// ## Returns
+    ///
+    /// `None` if no trading day can be represented after `date`.
+    pub fn next_trading_day(&self, date: NaiveDate) -> Option<NaiveDate> {
+        date.iter_days()
+            .skip(1)
+            .find(|&day| self.is_trading_day(day))
+    }
+
+    /// Get the trading days in the range `[from, to)`.
+    ///
+    /// An empty list is returned when `to` is not after `from`.
+    pub fn trading_days_between(&self, from: NaiveDate, to: NaiveDate) -> Vec<NaiveDate> {
+        from.iter_days()
+            .take_while(|&day| day < to)
+            .filter(|&day| self.is_trading_day(day))
+            .collect()
+    }
+}
+
+/// Compute the date of Easter Sunday of `year` (Gregorian calendar).
+fn easter_sunday(year: i32) -> Option<NaiveDate> {
+    // Anonymous Gregorian algorithm (Meeus/Jones/Butcher).
+    let a = year.rem_euclid(19);
+    let b = year.div_euclid(100);
+    let c = year.rem_euclid(100);
+    let d = b / 4;
+    let e = b % 4;
+    let f = (b + 8) / 25;
+    let g = (b - f + 1) / 3;
+    let h = (19 * a + b - d - g + 15) % 30;
+    let i = c / 4;
+    let k = c % 4;
+    let l = (32 + 2 * e + 2 * i - h - k) % 7;
+    let m = (a + 11 * h + 22 * l) / 451;
+    let month = (h + l - 7 * m + 114) / 31;
+    let day = (h + l - 7 * m + 114) % 31 + 1;
+
+    NaiveDate::from_ymd_opt(year, u32::try_from(month).ok()?, u32::try_from(day).ok()?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    /// Test case for the Easter holidays of several years.
+    #[rstest]
+    #[case::y2019(date(2019, 4, 19), date(2019, 4, 22))]
+    #[case::y2023(date(2023, 4, 7), date(2023, 4, 10))]
+    #[case::y2024(date(2024, 3, 29), date(2024, 4, 1))]
+    #[case::y2025(date(2025, 4, 18), date(2025, 4, 21))]
+    fn easter_holidays(#[case] good_friday: NaiveDate, #[case] easter_monday: NaiveDate) {
+        let calendar = TradingCalendar::new();
+
+        assert!(calendar.is_holiday(good_friday));
+        assert!(calendar.is_holiday(easter_monday));
+        assert!(!calendar.is_holiday(good_friday.pred_opt().unwrap()));
+        assert!(!calendar.is_holiday(easter_monday.succ_opt().unwrap()));
+    }
+
+    /// Test case for the next trading day across a weekend and holidays.
+    #[rstest]
+    #[case::regular_day(date(2025, 4, 8), date(2025, 4, 9))]
+    #[case::weekend(date(2025, 4, 11), date(2025, 4, 14))]
+    #[case::easter(date(2025, 4, 17), date(2025, 4, 22))]
+    #[case::christmas(date(2025, 12, 24), date(2025, 12, 29))]
+    fn next_trading_day(#[case] from: NaiveDate, #[case] expected: NaiveDate) {
+        assert_eq!(
+            TradingCalendar::new().next_trading_day(from),
+            Some(expected)
+        );
+    }
+
+    /// Test case for an extra closure added on top of the rules.
+    #[rstest]
+    fn extra_holidays() {
+        let calendar = TradingCalendar::new().with_holidays([date(2025, 4, 22)]);
+
+        assert!(!calendar.is_trading_day(date(2025, 4, 22)));
+        assert_eq!(
+            calendar.next_trading_day(date(2025, 4, 17)),
+            Some(date(2025, 4, 23))
+        );
+    }
+
+    /// Test case for the trading days within a range.
+    #[rstest]
+    #[case::easter_week(date(2025, 4, 14), date(2025, 4, 23), 5)]
+    #[case::same_day(date(2025, 4, 14), date(2025, 4, 14), 0)]
+    #[case::reversed(date(2025, 4, 23), date(2025, 4, 14), 0)]
+    fn trading_days_between(#[case] from: NaiveDate, #[case] to: NaiveDate, #[case] days: usize) {
+        assert_eq!(
+            TradingCalendar::new().trading_days_between(from, to).len(),
+            days
+        );
+    }
+
+    /// Test case for extra closures given in TOML.
+    #[rstest]
+    #[case::dates("holidays = [2024-12-24, 2024-12-31]")]
+    #[case::strings(r#"holidays = ["2024-12-24", "2024-12-31"]"#)]
+    #[case::mixed(r#"holidays = [2024-12-24, "2024-12-31"]"#)]
+    fn toml_holidays(#[case] data: &str) {
+        let calendar = TradingCalendar::from_toml_str(data).unwrap();
+
+        assert_eq!(
+            calendar,
+            TradingCalendar::new().with_holidays([date(2024, 12, 24), date(2024, 12, 31)])
+        );
+    }
+
+    /// Test case for TOML descriptions rejected by [TradingCalendar::from_toml_str].
+    #[rstest]
+    #[case::datetime("holidays = [2024-12-24T10:00:00]")]
+    #[case::time("holidays = [10:00:00]")]
+    #[case::bad_string(r#"holidays = ["24/12/2024"]"#)]
+    #[case::number("holidays = [20241224]")]
+    #[case::missing("closures = [2024-12-24]")]
+    fn invalid_toml(#[case] data: &str) {
+        assert!(matches!(
+            TradingCalendar::from_toml_str(data),
+            Err(IbexError::InvalidCalendar(_))
+        ));
+    }
+}
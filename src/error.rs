@@ -0,0 +1,109 @@
+// Copyright 2024 Felipe Torres González
+
+//! Error type of the library.
+
+use crate::identifiers::IdError;
+use std::{fmt, io};
+
+/// Errors that might happen when loading or building Ibex objects.
+///
+/// # Description
+///
+/// Each variant identifies the cause of the failure, so callers can tell
+/// apart a file that could not be read, a file that is not valid TOML, and a
+/// descriptor with missing or invalid data.
+///
+/// New variants might be added in future versions, hence the enum is marked as
+/// `non_exhaustive`.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum IbexError {
+    /// The input file could not be read.
+    Io(io::Error),
+    /// The input data could not be parsed as TOML.
+    TomlParse(toml::de::Error),
+    /// A company descriptor lacks a mandatory field.
+    MissingField {
+        /// Ticker of the company whose descriptor is incomplete.
+        ticker: String,
+        /// Name of the missing field.
+        field: String,
+    },
+    /// An identifier (ticker, ISIN or NIF) is not valid.
+    Id(IdError),
+    /// A volume curve definition is not valid.
+    InvalidVolumeCurve(String),
+    /// A trading calendar definition is not valid.
+    InvalidCalendar(String),
+    /// A trading session configuration is not valid.
+    InvalidSession(String),
+    /// A blob name can't be used by a storage backend.
+    InvalidBlobName(String),
+}
+
+impl fmt::Display for IbexError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            IbexError::Io(e) => write!(f, "error reading the input file: {e}"),
+            IbexError::TomlParse(e) => write!(f, "could not parse the input as TOML: {e}"),
+            IbexError::MissingField { ticker, field } => {
+                write!(f, "the descriptor of {ticker} lacks the field {field:?}")
+            }
+            IbexError::Id(_) => write!(f, "invalid identifier"),
+            IbexError::InvalidVolumeCurve(s) => write!(f, "invalid volume curve: {s}"),
+            IbexError::InvalidCalendar(s) => write!(f, "invalid trading calendar: {s}"),
+            IbexError::InvalidSession(s) => write!(f, "invalid trading session: {s}"),
+            IbexError::InvalidBlobName(s) => write!(f, "invalid blob name: {s:?}"),
+        }
+    }
+}
+
+impl std::error::Error for IbexError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            IbexError::Io(e) => Some(e),
+            IbexError::TomlParse(e) => Some(e),
+            IbexError::Id(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for IbexError {
+    fn from(e: io::Error) -> Self {
+        IbexError::Io(e)
+    }
+}
+
+impl From<toml::de::Error> for IbexError {
+    fn from(e: toml::de::Error) -> Self {
+        IbexError::TomlParse(e)
+    }
+}
+
+impl From<IdError> for IbexError {
+    fn from(e: IdError) -> Self {
+        IbexError::Id(e)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+    use std::error::Error;
+
+    /// Test case for identifier errors wrapped by [IbexError].
+    #[rstest]
+    fn id_error_is_the_source() {
+        let id_error = IdError::InvalidIsin(String::from("ES0113900J38"));
+        let error = IbexError::from(id_error.clone());
+
+        assert!(matches!(error, IbexError::Id(_)));
+        assert_eq!(
+            error.source().map(|e| e.to_string()),
+            Some(id_error.to_string())
+        );
+    }
+}
@@ -0,0 +1,370 @@
+// Copyright 2024 Felipe Torres González
+
+//! Strongly-typed identifiers for listed companies.
+//!
+//! Companies are identified by several codes that are all plain text: the
+//! stock ticker, the ISIN and the Spanish tax identifier (NIF). Passing them
+//! around as bare strings makes it easy to swap them by mistake, so each one
+//! gets its own type, validated when it is built.
+
+use std::fmt;
+use std::str::FromStr;
+
+/// Error returned when a string is not a valid identifier.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IdError {
+    /// The string is not a valid stock ticker.
+    InvalidTicker(String),
+    /// The string is not a valid ISIN.
+    InvalidIsin(String),
+    /// The string is not a valid NIF.
+    InvalidNif(String),
+}
+
+impl fmt::Display for IdError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            IdError::InvalidTicker(s) => write!(f, "invalid ticker: {s:?}"),
+            IdError::InvalidIsin(s) => write!(f, "invalid ISIN: {s:?}"),
+            IdError::InvalidNif(s) => write!(f, "invalid NIF: {s:?}"),
+        }
+    }
+}
+
+impl std::error::Error for IdError {}
+
+/// Stock ticker of a company listed in the Spanish market, e.g. `SAN`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Ticker(String);
+
+impl Ticker {
+    /// Maximum length of a BME ticker.
+    pub const MAX_LEN: usize = 5;
+
+    /// Build a new ticker.
+    ///
+    /// # Description
+    ///
+    /// BME tickers have between 1 and [Ticker::MAX_LEN] characters, made of
+    /// uppercase ASCII letters and digits. Lowercase input is rejected, so
+    /// typos in descriptor files are caught rather than silently fixed. Use
+    /// [Ticker::normalized] for user input.
+    pub fn new(ticker: &str) -> Result<Self, IdError> {
+        if (1..=Ticker::MAX_LEN).contains(&ticker.len())
+            && ticker
+                .chars()
+                .all(|c| c.is_ascii_uppercase() || c.is_ascii_digit())
+        {
+            Ok(Ticker(ticker.to_string()))
+        } else {
+            Err(IdError::InvalidTicker(ticker.to_string()))
+        }
+    }
+
+    /// Build a new ticker from user input.
+    ///
+    /// # Description
+    ///
+    /// Surrounding whitespace is removed and the input is uppercased before
+    /// validation, so `" san"` and `"SAN"` build the same ticker. This makes
+    /// lookups keyed by [Ticker] case-insensitive.
+    pub fn normalized(ticker: &str) -> Result<Self, IdError> {
+        Ticker::new(&ticker.trim().to_ascii_uppercase())
+    }
+
+    /// Get the ticker as a string slice.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+/// International Securities Identification Number (ISO 6166), e.g. `ES0113900J37`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Isin(String);
+
+impl Isin {
+    /// Build a new ISIN.
+    ///
+    /// # Description
+    ///
+    /// An ISIN has 12 characters: a 2-letter country code, a 9-character
+    /// alphanumeric code and a trailing check digit. The check digit is
+    /// verified using the Luhn algorithm over the preceding characters, where
+    /// letters count as two digits (`A` = 10, ..., `Z` = 35).
+    pub fn new(isin: &str) -> Result<Self, IdError> {
+        let valid = match isin.as_bytes() {
+            [c1, c2, code @ .., check] => {
+                code.len() == 9
+                    && c1.is_ascii_uppercase()
+                    && c2.is_ascii_uppercase()
+                    && code
+                        .iter()
+                        .all(|b| b.is_ascii_uppercase() || b.is_ascii_digit())
+                    && check.is_ascii_digit()
+                    && isin
+                        .get(..11)
+                        .is_some_and(|payload| Isin::check_digit(payload) == check - b'0')
+            }
+            _ => false,
+        };
+
+        if valid {
+            Ok(Isin(isin.to_string()))
+        } else {
+            Err(IdError::InvalidIsin(isin.to_string()))
+        }
+    }
+
+    /// Get the ISIN as a string slice.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Compute the check digit of the first 11 characters of an ISIN.
+    fn check_digit(payload: &str) -> u8 {
+        // Luhn: double every other digit starting from the rightmost one.
+        let (sum, _) = payload
+            .chars()
+            .rev()
+            .filter_map(|c| c.to_digit(36))
+            .flat_map(|v| std::iter::once(v % 10).chain((v > 9).then_some(v / 10)))
+            .fold((0, true), |(sum, double), d| {
+                let d = if double { d * 2 / 10 + d * 2 % 10 } else { d };
+                (sum + d, !double)
+            });
+
+        ((10 - sum % 10) % 10) as u8
+    }
+
+    /// Get the country code of the ISIN, e.g. `ES`.
+    pub fn country(&self) -> &str {
+        self.0.get(..2).unwrap_or_default()
+    }
+}
+
+/// Spanish tax identification number (NIF) of a company, e.g. `A39000013`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Nif(String);
+
+impl Nif {
+    /// Build a new NIF.
+    ///
+    /// # Description
+    ///
+    /// Companies use the NIF format formerly known as CIF: an uppercase letter
+    /// that tells the kind of entity, 7 digits and a control character. The
+    /// control character is computed from the digits, and it is a digit
+    /// (entities `A`, `B`, `E` and `H`), a letter from `J` = 0 to `I` = 9
+    /// (entities `N`, `P`, `Q`, `R`, `S` and `W`) or either of them (the rest
+    /// of the entities).
+    pub fn new(nif: &str) -> Result<Self, IdError> {
+        let valid = match nif.as_bytes() {
+            [entity, digits @ .., control] if digits.len() == 7 => {
+                digits.iter().all(u8::is_ascii_digit)
+                    && Nif::valid_control(*entity, Nif::control_digit(digits), *control)
+            }
+            _ => false,
+        };
+
+        if valid {
+            Ok(Nif(nif.to_string()))
+        } else {
+            Err(IdError::InvalidNif(nif.to_string()))
+        }
+    }
+
+    /// Compute the control digit of the 7 digits of a NIF.
+    fn control_digit(digits: &[u8]) -> u8 {
+        // Digits in odd positions (1st, 3rd, ...) are doubled and their own
+        // digits added, the ones in even positions are added as they are.
+        let sum: u8 = digits
+            .iter()
+            .map(|d| d - b'0')
+            .enumerate()
+            .map(|(i, d)| {
+                if i % 2 == 0 {
+                    d * 2 / 10 + d * 2 % 10
+                } else {
+                    d
+                }
+            })
+            .sum();
+
+        (10 - sum % 10) % 10
+    }
+
+    /// Check whether `control` is the expected control character for `entity`.
+    fn valid_control(entity: u8, digit: u8, control: u8) -> bool {
+        let as_digit = control == b'0' + digit;
+        let as_letter = b"JABCDEFGHI".get(usize::from(digit)) == Some(&control);
+
+        match entity {
+            b'A' | b'B' | b'E' | b'H' => as_digit,
+            b'N' | b'P' | b'Q' | b'R' | b'S' | b'W' => as_letter,
+            b'C' | b'D' | b'F' | b'G' | b'J' | b'U' | b'V' => as_digit || as_letter,
+            _ => false,
+        }
+    }
+
+    /// Get the NIF as a string slice.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+macro_rules! impl_id_traits {
+    ($($id:ty),*) => {
+        $(
+            impl fmt::Display for $id {
+                fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                    f.write_str(&self.0)
+                }
+            }
+
+            impl FromStr for $id {
+                type Err = IdError;
+
+                fn from_str(s: &str) -> Result<Self, Self::Err> {
+                    <$id>::new(s)
+                }
+            }
+
+            impl AsRef<str> for $id {
+                fn as_ref(&self) -> &str {
+                    &self.0
+                }
+            }
+        )*
+    };
+}
+
+impl_id_traits!(Ticker, Isin, Nif);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+
+    fn round_trip<T>(input: &str)
+    where
+        T: FromStr<Err = IdError> + fmt::Display + AsRef<str>,
+    {
+        let id: T = input.parse().unwrap();
+        assert_eq!(id.to_string(), input);
+        assert_eq!(id.as_ref(), input);
+    }
+
+    /// Test case for tickers following the BME conventions.
+    #[rstest]
+    #[case::one_char("A")]
+    #[case::three_chars("SAN")]
+    #[case::digits("A3M")]
+    #[case::max_len("ABCDE")]
+    fn valid_ticker(#[case] ticker: &str) {
+        assert_eq!(
+            Ticker::new(ticker).map(|t| t.to_string()),
+            Ok(ticker.to_string())
+        );
+    }
+
+    /// Test case for strings that must be rejected as ticker.
+    #[rstest]
+    #[case::lowercase("san")]
+    #[case::mixed_case("San")]
+    #[case::empty("")]
+    #[case::too_long("ABCDEF")]
+    #[case::punctuation("SAN.")]
+    #[case::dash("A-B")]
+    #[case::whitespace(" SAN")]
+    #[case::non_ascii("ÑA")]
+    fn invalid_ticker(#[case] ticker: &str) {
+        assert_eq!(
+            Ticker::new(ticker),
+            Err(IdError::InvalidTicker(ticker.to_string()))
+        );
+    }
+
+    /// Test case for user input, which is normalized before validation.
+    #[rstest]
+    #[case::lowercase("san")]
+    #[case::mixed_case("San")]
+    #[case::whitespace(" san ")]
+    #[case::already_valid("SAN")]
+    fn normalized_ticker(#[case] input: &str) {
+        assert_eq!(Ticker::normalized(input), Ticker::new("SAN"));
+    }
+
+    /// Test case for user input that is not a ticker even after normalization.
+    #[rstest]
+    #[case::empty("")]
+    #[case::blank("   ")]
+    #[case::too_long("abcdef")]
+    #[case::punctuation("sa.n")]
+    fn invalid_normalized_ticker(#[case] input: &str) {
+        assert!(Ticker::normalized(input).is_err());
+    }
+
+    /// Test case for valid ISINs, whose check digit must be accepted.
+    #[rstest]
+    #[case::santander("ES0113900J37")]
+    #[case::iberdrola("ES0144580Y14")]
+    #[case::apple("US0378331005")]
+    #[case::letters_in_code("AU0000XVGZA3")]
+    fn valid_isin(#[case] isin: &str) {
+        assert_eq!(Isin::new(isin).map(|i| i.to_string()), Ok(isin.to_string()));
+    }
+
+    /// Test case for strings that must be rejected as ISIN.
+    #[rstest]
+    #[case::wrong_check_digit("ES0113900J38")]
+    #[case::too_short("ES0113900J3")]
+    #[case::too_long("ES0113900J370")]
+    #[case::empty("")]
+    #[case::lowercase_country("es0113900J37")]
+    #[case::lowercase_code("ES0113900j37")]
+    #[case::letter_check_digit("ES0113900J3A")]
+    #[case::non_ascii("ÉS0113900J37")]
+    #[case::non_ascii_same_length("ES011390ÑJ7")]
+    fn invalid_isin(#[case] isin: &str) {
+        assert_eq!(Isin::new(isin), Err(IdError::InvalidIsin(isin.to_string())));
+    }
+
+    /// Test case for valid company NIFs, whose control character must be accepted.
+    #[rstest]
+    #[case::santander("A39000013")]
+    #[case::iberdrola("A48010615")]
+    #[case::telefonica("A28015865")]
+    #[case::letter_control("Q2818015F")]
+    #[case::either_as_digit("G28180156")]
+    #[case::either_as_letter("G2818015F")]
+    fn valid_nif(#[case] nif: &str) {
+        assert_eq!(Nif::new(nif).map(|n| n.to_string()), Ok(nif.to_string()));
+    }
+
+    /// Test case for strings that must be rejected as NIF.
+    #[rstest]
+    #[case::isin_prefix("ES0113900")]
+    #[case::all_letters("AAAAAAAAA")]
+    #[case::wrong_control("A39000014")]
+    #[case::letter_for_digit_entity("A3900001C")]
+    #[case::digit_for_letter_entity("Q28180156")]
+    #[case::unknown_entity("I39000013")]
+    #[case::personal_nif("12345678Z")]
+    #[case::lowercase("a39000013")]
+    #[case::too_short("A3900001")]
+    #[case::too_long("A390000133")]
+    #[case::empty("")]
+    #[case::non_ascii("Ñ3900001")]
+    fn invalid_nif(#[case] nif: &str) {
+        assert_eq!(Nif::new(nif), Err(IdError::InvalidNif(nif.to_string())));
+    }
+
+    /// Test case for the traits shared by all the identifiers.
+    #[rstest]
+    fn from_str_display_round_trip() {
+        round_trip::<Ticker>("SAN");
+        round_trip::<Isin>("ES0113900J37");
+        round_trip::<Nif>("A39000013");
+    }
+}
@@ -0,0 +1,40 @@
+// Copyright 2024 Felipe Torres González
+
+//! Finance library implementation for the Spanish Ibex indexes.
+//!
+//! This crate implements the API defined by [finance_api] for the indexes of
+//! the Spanish stock market managed by BME.
+//!
+//! [finance_api]: https://crates.io/crates/finance_api
+//!
+//! Library code never panics on external data: malformed input is reported
+//! through `Result` values. The lints below enforce that at compile time.
+#![cfg_attr(
+    not(test),
+    deny(
+        clippy::unwrap_used,
+        clippy::expect_used,
+        clippy::panic,
+        clippy::indexing_slicing
+    )
+)]
+
+pub mod calendar;
+pub mod error;
+pub mod identifiers;
+pub mod numbers;
+pub mod session;
+pub mod storage;
+pub mod symbols;
+pub mod volume;
+
+pub use calendar::TradingCalendar;
+pub use error::IbexError;
+pub use identifiers::{IdError, Isin, Nif, Ticker};
+pub use numbers::{parse_decimal, NumberLocale, ParseNumberError};
+pub use session::{
+    BmeSession, Clock, SessionConfig, SessionConfigBuilder, SessionPhase, SystemClock,
+};
+pub use storage::{FileStorage, MemoryStorage, Storage};
+pub use symbols::{SymbolConverter, SymbolVendor};
+pub use volume::{VolumeBucket, VolumeCurve};
@@ -0,0 +1,165 @@
+// Copyright 2024 Felipe Torres González
+
+//! Locale-aware parsing of numeric fields.
+//!
+//! Data files coming from Spanish sources write numbers as `1.234,56`: a comma
+//! as decimal separator and dots grouping thousands. Files coming from other
+//! sources use the English convention `1,234.56`. The functions of this module
+//! parse both without any preprocessing of the input.
+
+use std::fmt;
+
+/// Convention used to write decimal numbers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum NumberLocale {
+    /// Dot as decimal separator, comma as thousands separator: `1,234.56`.
+    #[default]
+    English,
+    /// Comma as decimal separator, dot as thousands separator: `1.234,56`.
+    Spanish,
+}
+
+impl NumberLocale {
+    /// Character used to separate the decimal part.
+    pub fn decimal_separator(self) -> char {
+        match self {
+            NumberLocale::English => '.',
+            NumberLocale::Spanish => ',',
+        }
+    }
+
+    /// Character used to group thousands.
+    pub fn thousands_separator(self) -> char {
+        match self {
+            NumberLocale::English => ',',
+            NumberLocale::Spanish => '.',
+        }
+    }
+}
+
+/// Error returned when a string is not a number written in the expected locale.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseNumberError {
+    input: String,
+    locale: NumberLocale,
+}
+
+impl fmt::Display for ParseNumberError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:?} is not a valid {:?} number",
+            self.input, self.locale
+        )
+    }
+}
+
+impl std::error::Error for ParseNumberError {}
+
+/// Parse a decimal number written following the conventions of `locale`.
+///
+/// # Description
+///
+/// Leading and trailing whitespace and a leading sign are accepted. The
+/// integer part is mandatory, and when a decimal separator is present it must
+/// be followed by at least one digit, so `,5` and `5,` are rejected as Spanish
+/// numbers.
+///
+/// Thousands separators are optional, but when present they must split the
+/// integer part in groups of three digits, and the first group can't start
+/// with `0`. Thus `1.234,5` is accepted as a Spanish number while `12.34` and
+/// `0.234,5` are rejected.
+pub fn parse_decimal(input: &str, locale: NumberLocale) -> Result<f64, ParseNumberError> {
+    let error = || ParseNumberError {
+        input: input.to_string(),
+        locale,
+    };
+    let is_digits = |s: &str| !s.is_empty() && s.chars().all(|c| c.is_ascii_digit());
+
+    let trimmed = input.trim();
+    let (sign, unsigned) = match trimmed.strip_prefix('-') {
+        Some(rest) => ("-", rest),
+        None => ("", trimmed.strip_prefix('+').unwrap_or(trimmed)),
+    };
+
+    let mut parts = unsigned.split(locale.decimal_separator());
+    let integer = parts.next().unwrap_or_default();
+    let fraction = parts.next();
+    if parts.next().is_some() {
+        return Err(error());
+    }
+
+    let mut groups = integer.split(locale.thousands_separator());
+    let mut digits = String::with_capacity(unsigned.len() + 1);
+    let first = groups.next().unwrap_or_default();
+    if !is_digits(first) {
+        return Err(error());
+    }
+    let grouped = integer.contains(locale.thousands_separator());
+    if grouped && (first.len() > 3 || first.starts_with('0')) {
+        return Err(error());
+    }
+    digits.push_str(first);
+    for group in groups {
+        if group.len() != 3 || !is_digits(group) {
+            return Err(error());
+        }
+        digits.push_str(group);
+    }
+
+    if let Some(fraction) = fraction {
+        if !is_digits(fraction) {
+            return Err(error());
+        }
+        digits.push('.');
+        digits.push_str(fraction);
+    }
+
+    format!("{sign}{digits}").parse().map_err(|_| error())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+
+    /// Test case for numbers that must be parsed.
+    #[rstest]
+    #[case::es_grouped("1.234,56", NumberLocale::Spanish, 1234.56)]
+    #[case::es_grouped_no_fraction("1.234", NumberLocale::Spanish, 1234.0)]
+    #[case::es_plain("1234,5", NumberLocale::Spanish, 1234.5)]
+    #[case::es_zero_fraction("0,5", NumberLocale::Spanish, 0.5)]
+    #[case::es_negative("-0,5", NumberLocale::Spanish, -0.5)]
+    #[case::es_millions("12.345.678,9", NumberLocale::Spanish, 12345678.9)]
+    #[case::en_grouped("1,234.56", NumberLocale::English, 1234.56)]
+    #[case::en_positive("+1,234", NumberLocale::English, 1234.0)]
+    #[case::en_whitespace(" 3 ", NumberLocale::English, 3.0)]
+    #[case::en_leading_zero("007", NumberLocale::English, 7.0)]
+    fn accepted(#[case] input: &str, #[case] locale: NumberLocale, #[case] expected: f64) {
+        assert_eq!(parse_decimal(input, locale), Ok(expected));
+    }
+
+    /// Test case for inputs that must be rejected.
+    #[rstest]
+    #[case::es_short_group("12.34", NumberLocale::Spanish)]
+    #[case::es_long_first_group("1234.567", NumberLocale::Spanish)]
+    #[case::es_zero_first_group("0.234,5", NumberLocale::Spanish)]
+    #[case::es_no_integer(",5", NumberLocale::Spanish)]
+    #[case::es_negative_no_integer("-,5", NumberLocale::Spanish)]
+    #[case::es_no_fraction("5,", NumberLocale::Spanish)]
+    #[case::es_two_decimal_separators("1,2,3", NumberLocale::Spanish)]
+    #[case::es_english_number("1,234.56", NumberLocale::Spanish)]
+    #[case::en_short_group("1.2.3", NumberLocale::English)]
+    #[case::en_zero_first_group("0,234.5", NumberLocale::English)]
+    #[case::en_no_integer(".5", NumberLocale::English)]
+    #[case::en_negative_no_integer("-.5", NumberLocale::English)]
+    #[case::en_no_fraction("5.", NumberLocale::English)]
+    #[case::en_exponent("1.5e3", NumberLocale::English)]
+    #[case::en_sign_only("-", NumberLocale::English)]
+    #[case::en_empty("", NumberLocale::English)]
+    #[case::en_double_sign("--5", NumberLocale::English)]
+    fn rejected(#[case] input: &str, #[case] locale: NumberLocale) {
+        assert!(parse_decimal(input, locale).is_err());
+    }
+}
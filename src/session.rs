@@ -0,0 +1,630 @@
+// Copyright 2024 Felipe Torres González
+
+//! Trading session phases of the Spanish stock market.
+//!
+//! A BME trading day goes through the following phases (Madrid time):
+//! - Opening auction, from 08:30 to 09:00.
+//! - Continuous trading, from 09:00 to 17:30.
+//! - Closing auction, from 17:30 to 17:35.
+//!
+//! Outside those phases, and on non-trading days, the market is closed. On
+//! December 24th and 31st continuous trading ends early, at 14:00.
+//!
+//! The schedule is described by a [SessionConfig], so other markets can reuse
+//! the same machinery with their own hours.
+
+use crate::calendar::TradingCalendar;
+use crate::error::IbexError;
+use chrono::{
+    DateTime, Datelike, Duration, LocalResult, NaiveDate, NaiveDateTime, NaiveTime, Offset,
+    TimeZone, Timelike, Utc,
+};
+use chrono_tz::Europe::Madrid;
+use chrono_tz::Tz;
+use std::collections::BTreeMap;
+
+/// Phase of a trading session.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SessionPhase {
+    /// Opening auction: orders are accepted but not matched.
+    PreOpen,
+    /// Continuous trading.
+    Continuous,
+    /// Closing auction: orders are accepted but not matched until the end.
+    ClosingAuction,
+    /// The market is closed.
+    Closed,
+}
+
+/// Schedule of a trading session.
+///
+/// # Description
+///
+/// Times are expressed in the local time of the market, given by its
+/// timezone. Auctions are described by their length: the opening auction
+/// ends when continuous trading starts, and the closing auction starts when
+/// continuous trading ends.
+///
+/// Use [SessionConfig::builder] to describe a custom schedule, or
+/// [SessionConfig::bme] to get the schedule of the BME continuous market.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SessionConfig {
+    timezone: Tz,
+    open: NaiveTime,
+    close: NaiveTime,
+    opening_auction: Duration,
+    closing_auction: Duration,
+    half_days: BTreeMap<NaiveDate, NaiveTime>,
+    annual_half_days: BTreeMap<(u32, u32), NaiveTime>,
+}
+
+impl SessionConfig {
+    /// Get a builder to describe a custom schedule.
+    pub fn builder() -> SessionConfigBuilder {
+        SessionConfigBuilder::default()
+    }
+
+    /// Schedule of the BME continuous market.
+    pub fn bme() -> Self {
+        const OPEN: NaiveTime = NaiveTime::from_hms_opt(9, 0, 0).expect("09:00 is a valid time");
+        const CLOSE: NaiveTime = NaiveTime::from_hms_opt(17, 30, 0).expect("17:30 is a valid time");
+        const HALF_DAY_CLOSE: NaiveTime =
+            NaiveTime::from_hms_opt(14, 0, 0).expect("14:00 is a valid time");
+
+        SessionConfig {
+            timezone: Madrid,
+            open: OPEN,
+            close: CLOSE,
+            opening_auction: Duration::minutes(30),
+            closing_auction: Duration::minutes(5),
+            half_days: BTreeMap::new(),
+            annual_half_days: BTreeMap::from([
+                ((12, 24), HALF_DAY_CLOSE),
+                ((12, 31), HALF_DAY_CLOSE),
+            ]),
+        }
+    }
+
+    /// Get the timezone of the market.
+    pub fn timezone(&self) -> Tz {
+        self.timezone
+    }
+
+    /// Get the local time in which continuous trading starts.
+    pub fn open(&self) -> NaiveTime {
+        self.open
+    }
+
+    /// Get the local time in which continuous trading ends on regular days.
+    pub fn close(&self) -> NaiveTime {
+        self.close
+    }
+
+    /// Get the length of the opening auction.
+    pub fn opening_auction(&self) -> Duration {
+        self.opening_auction
+    }
+
+    /// Get the length of the closing auction.
+    pub fn closing_auction(&self) -> Duration {
+        self.closing_auction
+    }
+
+    /// Get the local time in which continuous trading ends on `date`.
+    ///
+    /// Half-day overrides for `date` take precedence over the regular close.
+    pub fn close_on(&self, date: NaiveDate) -> NaiveTime {
+        self.half_days
+            .get(&date)
+            .or_else(|| self.annual_half_days.get(&(date.month(), date.day())))
+            .copied()
+            .unwrap_or(self.close)
+    }
+}
+
+impl Default for SessionConfig {
+    fn default() -> Self {
+        SessionConfig::bme()
+    }
+}
+
+/// Builder of [SessionConfig] objects.
+///
+/// # Description
+///
+/// Every setting starts with the value of the BME schedule, so only the
+/// settings that differ need to be given. Half-day overrides are not
+/// inherited from the BME schedule.
+#[derive(Debug, Clone)]
+pub struct SessionConfigBuilder {
+    config: SessionConfig,
+}
+
+impl Default for SessionConfigBuilder {
+    fn default() -> Self {
+        let mut config = SessionConfig::bme();
+        config.annual_half_days.clear();
+
+        SessionConfigBuilder { config }
+    }
+}
+
+impl SessionConfigBuilder {
+    /// Set the timezone in which the session times are expressed.
+    pub fn timezone(mut self, timezone: Tz) -> Self {
+        self.config.timezone = timezone;
+        self
+    }
+
+    /// Set the local time in which continuous trading starts.
+    pub fn open(mut self, open: NaiveTime) -> Self {
+        self.config.open = open;
+        self
+    }
+
+    /// Set the local time in which continuous trading ends.
+    pub fn close(mut self, close: NaiveTime) -> Self {
+        self.config.close = close;
+        self
+    }
+
+    /// Set the length of the opening auction.
+    pub fn opening_auction(mut self, length: Duration) -> Self {
+        self.config.opening_auction = length;
+        self
+    }
+
+    /// Set the length of the closing auction.
+    pub fn closing_auction(mut self, length: Duration) -> Self {
+        self.config.closing_auction = length;
+        self
+    }
+
+    /// End continuous trading at `close` on `date`.
+    pub fn half_day(mut self, date: NaiveDate, close: NaiveTime) -> Self {
+        self.config.half_days.insert(date, close);
+        self
+    }
+
+    /// End continuous trading at `close` on the given day of every year.
+    pub fn annual_half_day(mut self, month: u32, day: u32, close: NaiveTime) -> Self {
+        self.config.annual_half_days.insert((month, day), close);
+        self
+    }
+
+    /// Build the [SessionConfig].
+    ///
+    /// # Description
+    ///
+    /// The schedule is checked for consistency: auctions must not have a
+    /// negative length, continuous trading must start before it ends (also on
+    /// half days), and the whole session, auctions included, must fit within
+    /// a single day.
+    pub fn build(self) -> Result<SessionConfig, IbexError> {
+        let config = self.config;
+        let invalid = |msg: &str| Err(IbexError::InvalidSession(msg.to_string()));
+        let seconds = |time: NaiveTime| i64::from(time.num_seconds_from_midnight());
+
+        if config.opening_auction < Duration::zero() || config.closing_auction < Duration::zero() {
+            return invalid("auctions must not have a negative length");
+        }
+        if seconds(config.open) < config.opening_auction.num_seconds() {
+            return invalid("the opening auction must start after midnight");
+        }
+
+        let closes = config
+            .half_days
+            .values()
+            .chain(config.annual_half_days.values())
+            .chain(std::iter::once(&config.close));
+        for &close in closes {
+            if close <= config.open {
+                return invalid("continuous trading must end after it starts");
+            }
+            if seconds(close) + config.closing_auction.num_seconds() >= 24 * 3600 {
+                return invalid("the closing auction must end before midnight");
+            }
+        }
+
+        Ok(config)
+    }
+}
+
+/// Source of the current instant.
+///
+/// # Description
+///
+/// [BmeSession] asks its clock for the current instant, so tests can replace
+/// the system clock with a fixed one to check session boundaries, holidays
+/// and DST changes deterministically.
+pub trait Clock {
+    /// Get the current instant.
+    fn now(&self) -> DateTime<Utc>;
+}
+
+/// [Clock] that reads the system time.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+}
+
+/// Trading session of the BME continuous market.
+///
+/// # Description
+///
+/// The session reads the current instant from a [Clock], which is the system
+/// clock unless another one is given with [BmeSession::with_clock].
+#[derive(Debug, Clone)]
+pub struct BmeSession<C: Clock = SystemClock> {
+    calendar: TradingCalendar,
+    config: SessionConfig,
+    clock: C,
+}
+
+impl BmeSession {
+    /// Build the BME session using `calendar` to tell trading days apart.
+    pub fn new(calendar: TradingCalendar) -> Self {
+        BmeSession::with_config(calendar, SessionConfig::bme())
+    }
+
+    /// Build a session following a custom schedule.
+    pub fn with_config(calendar: TradingCalendar, config: SessionConfig) -> Self {
+        BmeSession {
+            calendar,
+            config,
+            clock: SystemClock,
+        }
+    }
+}
+
+impl<C: Clock> BmeSession<C> {
+    /// Replace the clock used to get the current instant.
+    pub fn with_clock<D: Clock>(self, clock: D) -> BmeSession<D> {
+        BmeSession {
+            calendar: self.calendar,
+            config: self.config,
+            clock,
+        }
+    }
+
+    /// Get the trading calendar used by the session.
+    pub fn calendar(&self) -> &TradingCalendar {
+        &self.calendar
+    }
+
+    /// Get the schedule of the session.
+    pub fn config(&self) -> &SessionConfig {
+        &self.config
+    }
+
+    /// Get the phase of the session at a given market local time.
+    pub fn phase_at_local(&self, local: NaiveDateTime) -> SessionPhase {
+        let date = local.date();
+        if !self.calendar.is_trading_day(date) {
+            return SessionPhase::Closed;
+        }
+
+        let time = local.time();
+        let open = self.config.open;
+        let close = self.config.close_on(date);
+        if time < open - self.config.opening_auction || time >= close + self.config.closing_auction
+        {
+            SessionPhase::Closed
+        } else if time < open {
+            SessionPhase::PreOpen
+        } else if time < close {
+            SessionPhase::Continuous
+        } else {
+            SessionPhase::ClosingAuction
+        }
+    }
+
+    /// Get the phase of the session at a given instant.
+    pub fn phase_at(&self, instant: DateTime<Utc>) -> SessionPhase {
+        self.phase_at_local(instant.with_timezone(&self.config.timezone).naive_local())
+    }
+
+    /// Get the instant in which continuous trading starts on `date`.
+    ///
+    /// # Description
+    ///
+    /// The market opens at a fixed local time, which for BME is 08:00 UTC in
+    /// winter (CET) and 07:00 UTC in summer (CEST).
+    ///
+    /// Custom schedules might open at a local time that happens twice when
+    /// clocks go back, which resolves to the earliest instant, or that is
+    /// skipped when clocks go forward, which is moved forward by the length of
+    /// the gap.
+    ///
+    /// ## Returns
+    ///
+    /// `None` if the market does not trade on `date`.
+    pub fn open_utc(&self, date: NaiveDate) -> Option<DateTime<Utc>> {
+        self.local_to_utc(date, self.config.open)
+    }
+
+    /// Get the instant in which continuous trading ends on `date`.
+    ///
+    /// # Description
+    ///
+    /// Ambiguous and skipped local times are resolved as in [BmeSession::open_utc].
+    ///
+    /// ## Returns
+    ///
+    /// `None` if the market does not trade on `date`.
+    pub fn close_utc(&self, date: NaiveDate) -> Option<DateTime<Utc>> {
+        self.local_to_utc(date, self.config.close_on(date))
+    }
+
+    fn local_to_utc(&self, date: NaiveDate, time: NaiveTime) -> Option<DateTime<Utc>> {
+        if !self.calendar.is_trading_day(date) {
+            return None;
+        }
+
+        let timezone = self.config.timezone;
+        let local = date.and_time(time);
+        match timezone.from_local_datetime(&local) {
+            LocalResult::Single(instant) | LocalResult::Ambiguous(instant, _) => {
+                Some(instant.with_timezone(&Utc))
+            }
+            // The local time is skipped by a DST change: apply the offset in
+            // force before the gap, which moves it forward by the gap length.
+            LocalResult::None => {
+                let before = timezone
+                    .offset_from_utc_datetime(&local.checked_sub_signed(Duration::days(1))?)
+                    .fix();
+                local
+                    .checked_sub_signed(Duration::seconds(before.local_minus_utc().into()))
+                    .map(|utc| utc.and_utc())
+            }
+        }
+    }
+
+    /// Get the phase of the session right now.
+    pub fn phase_now(&self) -> SessionPhase {
+        self.phase_at(self.clock.now())
+    }
+
+    /// Check whether continuous trading is running at a given instant.
+    pub fn is_open_at(&self, instant: DateTime<Utc>) -> bool {
+        self.phase_at(instant) == SessionPhase::Continuous
+    }
+
+    /// Check whether continuous trading is running right now.
+    pub fn is_open_now(&self) -> bool {
+        self.is_open_at(self.clock.now())
+    }
+}
+
+impl Default for BmeSession {
+    fn default() -> Self {
+        BmeSession::new(TradingCalendar::new())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+
+    /// [Clock] stuck at a given instant.
+    struct FixedClock(DateTime<Utc>);
+
+    impl Clock for FixedClock {
+        fn now(&self) -> DateTime<Utc> {
+            self.0
+        }
+    }
+
+    fn datetime(y: i32, m: u32, d: u32, h: u32, min: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(y, m, d)
+            .and_then(|date| date.and_hms_opt(h, min, 0))
+            .unwrap()
+    }
+
+    /// Test case for the phases around the session boundaries (Madrid time).
+    #[rstest]
+    #[case::before_pre_open(datetime(2025, 3, 12, 8, 29), SessionPhase::Closed)]
+    #[case::pre_open(datetime(2025, 3, 12, 8, 30), SessionPhase::PreOpen)]
+    #[case::open(datetime(2025, 3, 12, 9, 0), SessionPhase::Continuous)]
+    #[case::continuous(datetime(2025, 3, 12, 17, 29), SessionPhase::Continuous)]
+    #[case::closing_auction(datetime(2025, 3, 12, 17, 30), SessionPhase::ClosingAuction)]
+    #[case::closed(datetime(2025, 3, 12, 17, 35), SessionPhase::Closed)]
+    #[case::weekend(datetime(2025, 3, 15, 12, 0), SessionPhase::Closed)]
+    #[case::good_friday(datetime(2025, 4, 18, 12, 0), SessionPhase::Closed)]
+    fn phase_at_local(#[case] time: NaiveDateTime, #[case] expected: SessionPhase) {
+        assert_eq!(BmeSession::default().phase_at_local(time), expected);
+    }
+
+    /// Test case for the current phase read from an injected clock.
+    #[rstest]
+    #[case::pre_open(datetime(2025, 3, 12, 7, 30), SessionPhase::PreOpen, false)]
+    #[case::open(datetime(2025, 3, 12, 8, 0), SessionPhase::Continuous, true)]
+    #[case::closing_auction(datetime(2025, 3, 12, 16, 30), SessionPhase::ClosingAuction, false)]
+    #[case::closed(datetime(2025, 3, 12, 16, 35), SessionPhase::Closed, false)]
+    #[case::holiday(datetime(2025, 5, 1, 10, 0), SessionPhase::Closed, false)]
+    fn phase_now(#[case] utc: NaiveDateTime, #[case] phase: SessionPhase, #[case] open: bool) {
+        let session = BmeSession::default().with_clock(FixedClock(utc.and_utc()));
+
+        assert_eq!(session.phase_now(), phase);
+        assert_eq!(session.is_open_now(), open);
+    }
+
+    /// Test case for the UTC open and close times across DST changes.
+    #[rstest]
+    #[case::winter(datetime(2025, 1, 15, 8, 0), datetime(2025, 1, 15, 16, 30))]
+    #[case::summer(datetime(2025, 7, 15, 7, 0), datetime(2025, 7, 15, 15, 30))]
+    #[case::before_march_switch(datetime(2025, 3, 28, 8, 0), datetime(2025, 3, 28, 16, 30))]
+    #[case::after_march_switch(datetime(2025, 3, 31, 7, 0), datetime(2025, 3, 31, 15, 30))]
+    #[case::before_october_switch(datetime(2025, 10, 24, 7, 0), datetime(2025, 10, 24, 15, 30))]
+    #[case::after_october_switch(datetime(2025, 10, 27, 8, 0), datetime(2025, 10, 27, 16, 30))]
+    #[case::half_day(datetime(2025, 12, 24, 8, 0), datetime(2025, 12, 24, 13, 0))]
+    fn utc_open_close(#[case] open: NaiveDateTime, #[case] close: NaiveDateTime) {
+        let session = BmeSession::default();
+        let date = open.date();
+
+        assert_eq!(session.open_utc(date), Some(open.and_utc()));
+        assert_eq!(session.close_utc(date), Some(close.and_utc()));
+    }
+
+    /// Test case for the UTC open and close times of non-trading days.
+    #[rstest]
+    #[case::march_switch(date(2025, 3, 30))]
+    #[case::october_switch(date(2025, 10, 26))]
+    #[case::christmas(date(2025, 12, 25))]
+    fn utc_open_close_closed(#[case] date: NaiveDate) {
+        let session = BmeSession::default();
+
+        assert_eq!(session.open_utc(date), None);
+        assert_eq!(session.close_utc(date), None);
+    }
+
+    /// Test case for custom schedules whose times are skipped or repeated by a
+    /// DST change. Tehran moved clocks forward from 00:00 to 01:00 on
+    /// 2021-03-22, and back from 24:00 to 23:00 on 2021-09-21.
+    #[rstest]
+    #[case::skipped_open(
+        datetime(2021, 3, 22, 0, 30),
+        datetime(2021, 3, 22, 12, 0),
+        datetime(2021, 3, 21, 21, 0),
+        datetime(2021, 3, 22, 7, 30)
+    )]
+    #[case::repeated_close(
+        datetime(2021, 9, 21, 9, 0),
+        datetime(2021, 9, 21, 23, 30),
+        datetime(2021, 9, 21, 4, 30),
+        datetime(2021, 9, 21, 19, 0)
+    )]
+    fn utc_dst_transition(
+        #[case] open: NaiveDateTime,
+        #[case] close: NaiveDateTime,
+        #[case] open_utc: NaiveDateTime,
+        #[case] close_utc: NaiveDateTime,
+    ) {
+        let config = SessionConfig::builder()
+            .timezone(chrono_tz::Asia::Tehran)
+            .open(open.time())
+            .close(close.time())
+            .opening_auction(Duration::zero())
+            .closing_auction(Duration::zero())
+            .build()
+            .unwrap();
+        let session = BmeSession::with_config(TradingCalendar::new(), config);
+
+        assert_eq!(session.open_utc(open.date()), Some(open_utc.and_utc()));
+        assert_eq!(session.close_utc(close.date()), Some(close_utc.and_utc()));
+    }
+
+    /// Test case for the phase at the same UTC time on both sides of a DST change.
+    #[rstest]
+    #[case::before_march_switch(datetime(2025, 3, 28, 7, 30), SessionPhase::PreOpen)]
+    #[case::after_march_switch(datetime(2025, 3, 31, 7, 30), SessionPhase::Continuous)]
+    #[case::before_october_switch(datetime(2025, 10, 24, 7, 30), SessionPhase::Continuous)]
+    #[case::after_october_switch(datetime(2025, 10, 27, 7, 30), SessionPhase::PreOpen)]
+    #[case::summer_close(datetime(2025, 3, 31, 15, 32), SessionPhase::ClosingAuction)]
+    #[case::winter_close(datetime(2025, 10, 27, 15, 32), SessionPhase::Continuous)]
+    #[case::half_day_close(datetime(2025, 12, 24, 13, 2), SessionPhase::ClosingAuction)]
+    #[case::half_day_closed(datetime(2025, 12, 24, 13, 5), SessionPhase::Closed)]
+    fn phase_across_dst(#[case] utc: NaiveDateTime, #[case] expected: SessionPhase) {
+        assert_eq!(BmeSession::default().phase_at(utc.and_utc()), expected);
+    }
+
+    fn time(h: u32, m: u32) -> NaiveTime {
+        NaiveTime::from_hms_opt(h, m, 0).unwrap()
+    }
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    /// Test case for schedules rejected by [SessionConfigBuilder::build].
+    #[rstest]
+    #[case::negative_opening_auction(
+        SessionConfig::builder().opening_auction(Duration::minutes(-1)),
+        "auctions must not have a negative length"
+    )]
+    #[case::negative_closing_auction(
+        SessionConfig::builder().closing_auction(Duration::minutes(-1)),
+        "auctions must not have a negative length"
+    )]
+    #[case::opening_auction_before_midnight(
+        SessionConfig::builder().open(time(0, 10)),
+        "the opening auction must start after midnight"
+    )]
+    #[case::close_before_open(
+        SessionConfig::builder().close(time(8, 0)),
+        "continuous trading must end after it starts"
+    )]
+    #[case::close_at_open(
+        SessionConfig::builder().close(time(9, 0)),
+        "continuous trading must end after it starts"
+    )]
+    #[case::half_day_at_open(
+        SessionConfig::builder().half_day(date(2025, 12, 24), time(9, 0)),
+        "continuous trading must end after it starts"
+    )]
+    #[case::annual_half_day_before_open(
+        SessionConfig::builder().annual_half_day(12, 31, time(8, 0)),
+        "continuous trading must end after it starts"
+    )]
+    #[case::closing_auction_past_midnight(
+        SessionConfig::builder().close(time(23, 58)),
+        "the closing auction must end before midnight"
+    )]
+    #[case::half_day_auction_past_midnight(
+        SessionConfig::builder().half_day(date(2025, 12, 24), time(23, 56)),
+        "the closing auction must end before midnight"
+    )]
+    fn invalid_config(#[case] builder: SessionConfigBuilder, #[case] msg: &str) {
+        match builder.build() {
+            Err(IbexError::InvalidSession(e)) => assert_eq!(e, msg),
+            other => panic!("unexpected result: {other:?}"),
+        }
+    }
+
+    /// Test case for a custom schedule built from the BME defaults.
+    #[rstest]
+    fn custom_config() {
+        let config = SessionConfig::builder()
+            .open(time(9, 30))
+            .close(time(16, 0))
+            .opening_auction(Duration::zero())
+            .build()
+            .unwrap();
+
+        assert_eq!(config.timezone(), Madrid);
+        assert_eq!(config.open(), time(9, 30));
+        assert_eq!(config.close_on(date(2025, 12, 24)), time(16, 0));
+        assert_eq!(config.opening_auction(), Duration::zero());
+        assert_eq!(config.closing_auction(), Duration::minutes(5));
+    }
+
+    /// Test case for a half day taking precedence over an annual half day.
+    #[rstest]
+    #[case::half_day(date(2025, 12, 24), time(13, 0))]
+    #[case::annual_half_day(date(2026, 12, 24), time(14, 0))]
+    #[case::regular_day(date(2025, 12, 23), time(17, 30))]
+    fn half_day_overrides_annual(#[case] day: NaiveDate, #[case] close: NaiveTime) {
+        let config = SessionConfig::builder()
+            .annual_half_day(12, 24, time(14, 0))
+            .half_day(date(2025, 12, 24), time(13, 0))
+            .build()
+            .unwrap();
+
+        assert_eq!(config.close_on(day), close);
+    }
+
+    /// Test case for the early close of the BME schedule on December 24th and 31st.
+    #[rstest]
+    #[case::christmas_eve(date(2025, 12, 24), time(14, 0))]
+    #[case::new_years_eve(date(2025, 12, 31), time(14, 0))]
+    #[case::regular_day(date(2025, 12, 30), time(17, 30))]
+    fn bme_half_days(#[case] day: NaiveDate, #[case] close: NaiveTime) {
+        assert_eq!(SessionConfig::bme().close_on(day), close);
+    }
+}
@@ -0,0 +1,259 @@
+// Copyright 2024 Felipe Torres González
+
+//! Pluggable persistence of named blobs.
+//!
+//! Features that keep user data around (watchlists, position books,
+//! annotations, ...) persist it through the [Storage] trait rather than
+//! writing files on their own. Applications can thus redirect persistence to
+//! their own backend by implementing the trait.
+
+use crate::error::IbexError;
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// A backend able to load and save named blobs of data.
+pub trait Storage {
+    /// Load the blob identified by `name`.
+    ///
+    /// ## Returns
+    ///
+    /// `None` when no blob has been saved under `name`.
+    fn load(&self, name: &str) -> Result<Option<Vec<u8>>, IbexError>;
+
+    /// Save `data` under `name`, replacing any previous blob with that name.
+    fn save(&mut self, name: &str, data: &[u8]) -> Result<(), IbexError>;
+
+    /// Remove the blob identified by `name`, if any.
+    fn remove(&mut self, name: &str) -> Result<(), IbexError>;
+}
+
+/// A [Storage] that keeps every blob in memory.
+///
+/// Useful for tests and short-lived processes that do not need to keep data
+/// after exiting.
+#[derive(Debug, Clone, Default)]
+pub struct MemoryStorage {
+    blobs: HashMap<String, Vec<u8>>,
+}
+
+impl MemoryStorage {
+    /// Build an empty in-memory storage.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl Storage for MemoryStorage {
+    fn load(&self, name: &str) -> Result<Option<Vec<u8>>, IbexError> {
+        Ok(self.blobs.get(name).cloned())
+    }
+
+    fn save(&mut self, name: &str, data: &[u8]) -> Result<(), IbexError> {
+        self.blobs.insert(name.to_string(), data.to_vec());
+        Ok(())
+    }
+
+    fn remove(&mut self, name: &str) -> Result<(), IbexError> {
+        self.blobs.remove(name);
+        Ok(())
+    }
+}
+
+/// A [Storage] that keeps each blob as a file inside a directory.
+///
+/// # Description
+///
+/// Blob names map to file names inside the root directory, hence names must
+/// be plain file names: empty names and names containing path separators are
+/// rejected with [IbexError::InvalidBlobName]. Names starting with `.` are
+/// rejected as well, as that prefix is reserved for the temporary files
+/// written while saving.
+#[derive(Debug, Clone)]
+pub struct FileStorage {
+    root: PathBuf,
+}
+
+impl FileStorage {
+    /// Build a storage rooted at `root`. The directory is created on the first save.
+    pub fn new(root: impl AsRef<Path>) -> Self {
+        FileStorage {
+            root: root.as_ref().to_path_buf(),
+        }
+    }
+
+    /// Get the directory where blobs are stored.
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    fn blob_path(&self, name: &str) -> Result<PathBuf, IbexError> {
+        let invalid = name.is_empty() || name.starts_with('.') || name.contains(['/', '\\']);
+
+        if invalid {
+            Err(IbexError::InvalidBlobName(name.to_string()))
+        } else {
+            Ok(self.root.join(name))
+        }
+    }
+}
+
+impl Storage for FileStorage {
+    fn load(&self, name: &str) -> Result<Option<Vec<u8>>, IbexError> {
+        match fs::read(self.blob_path(name)?) {
+            Ok(data) => Ok(Some(data)),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Save `data` under `name`, replacing any previous blob with that name.
+    ///
+    /// # Description
+    ///
+    /// Data is first written to a temporary file inside the root directory,
+    /// which is then renamed over the blob. A crash in the middle of a save
+    /// thus leaves the previous blob untouched. Each save uses its own
+    /// temporary file, so concurrent saves never write to the same one.
+    fn save(&mut self, name: &str, data: &[u8]) -> Result<(), IbexError> {
+        static SAVES: AtomicU64 = AtomicU64::new(0);
+
+        let path = self.blob_path(name)?;
+        let tmp_path = self.root.join(format!(
+            ".{name}.{}.{}.tmp",
+            std::process::id(),
+            SAVES.fetch_add(1, Ordering::Relaxed)
+        ));
+
+        fs::create_dir_all(&self.root)?;
+        let result = fs::write(&tmp_path, data).and_then(|_| fs::rename(&tmp_path, &path));
+        if result.is_err() {
+            let _ = fs::remove_file(&tmp_path);
+        }
+
+        Ok(result?)
+    }
+
+    fn remove(&mut self, name: &str) -> Result<(), IbexError> {
+        match fs::remove_file(self.blob_path(name)?) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e.into()),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+
+    /// Temporary directory that is removed when dropped.
+    struct TempDir(PathBuf);
+
+    impl TempDir {
+        fn new(name: &str) -> Self {
+            let path =
+                std::env::temp_dir().join(format!("finance_ibex-{name}-{}", std::process::id()));
+            let _ = fs::remove_dir_all(&path);
+            TempDir(path)
+        }
+    }
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    fn check_round_trip(storage: &mut impl Storage) {
+        assert_eq!(storage.load("watchlist").unwrap(), None);
+
+        storage.save("watchlist", b"SAN\nBBVA").unwrap();
+        assert_eq!(
+            storage.load("watchlist").unwrap(),
+            Some(b"SAN\nBBVA".to_vec())
+        );
+
+        storage.save("watchlist", b"IBE").unwrap();
+        assert_eq!(storage.load("watchlist").unwrap(), Some(b"IBE".to_vec()));
+
+        storage.remove("watchlist").unwrap();
+        assert_eq!(storage.load("watchlist").unwrap(), None);
+        // Removing a missing blob is not an error.
+        storage.remove("watchlist").unwrap();
+    }
+
+    /// Test case for a save/load/remove cycle in memory.
+    #[rstest]
+    fn memory_round_trip() {
+        check_round_trip(&mut MemoryStorage::new());
+    }
+
+    /// Test case for a save/load/remove cycle on disk.
+    #[rstest]
+    fn file_round_trip() {
+        let dir = TempDir::new("round-trip");
+        let mut storage = FileStorage::new(dir.0.join("nested"));
+
+        check_round_trip(&mut storage);
+        // No temporary files are left behind.
+        assert_eq!(fs::read_dir(storage.root()).unwrap().count(), 0);
+    }
+
+    /// Test case for concurrent saves of the same blob from cloned storages.
+    #[rstest]
+    fn file_concurrent_saves() {
+        let dir = TempDir::new("concurrent");
+        let storage = FileStorage::new(&dir.0);
+
+        let handles: Vec<_> = [b"SAN", b"IBE"]
+            .into_iter()
+            .map(|data| {
+                let mut storage = storage.clone();
+                std::thread::spawn(move || {
+                    (0..50)
+                        .map(|_| storage.save("watchlist", data))
+                        .collect::<Result<Vec<_>, _>>()
+                })
+            })
+            .collect();
+        for handle in handles {
+            handle.join().unwrap().unwrap();
+        }
+
+        let data = storage.load("watchlist").unwrap().unwrap();
+        assert!(data == b"SAN" || data == b"IBE");
+        // Only the blob is left in the directory.
+        assert_eq!(fs::read_dir(storage.root()).unwrap().count(), 1);
+    }
+
+    /// Test case for names that can't be used as blob names.
+    #[rstest]
+    #[case::empty("")]
+    #[case::current_dir(".")]
+    #[case::parent_dir("..")]
+    #[case::hidden(".watchlist")]
+    #[case::temporary(".watchlist.1234.0.tmp")]
+    #[case::nested("a/b")]
+    #[case::windows_nested("a\\b")]
+    fn file_invalid_names(#[case] name: &str) {
+        // Invalid names are rejected before touching the filesystem.
+        let mut storage = FileStorage::new(std::env::temp_dir().join("finance_ibex-unused"));
+
+        assert!(matches!(
+            storage.save(name, b"data"),
+            Err(IbexError::InvalidBlobName(n)) if n == name
+        ));
+        assert!(matches!(
+            storage.load(name),
+            Err(IbexError::InvalidBlobName(_))
+        ));
+        assert!(matches!(
+            storage.remove(name),
+            Err(IbexError::InvalidBlobName(_))
+        ));
+    }
+}
@@ -0,0 +1,245 @@
+// Copyright 2024 Felipe Torres González
+
+//! Conversion between BME tickers and data vendor symbols.
+//!
+//! Data vendors identify stocks listed in the Spanish market using the BME
+//! ticker plus a suffix that indicates the exchange. Most constituents follow
+//! that rule, e.g. _SAN_ becomes `SAN.MC` both for Yahoo Finance and for
+//! Reuters (RIC). The exceptions can be registered in a [SymbolConverter].
+
+use crate::identifiers::{IdError, Ticker};
+use std::collections::HashMap;
+
+/// Data vendors whose symbology is supported.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SymbolVendor {
+    /// Yahoo Finance symbols, e.g. `SAN.MC`.
+    Yahoo,
+    /// Reuters Instrument Codes (RIC), e.g. `SAN.MC`.
+    Reuters,
+}
+
+impl SymbolVendor {
+    /// Suffix appended by the vendor to the BME ticker.
+    pub fn suffix(self) -> &'static str {
+        match self {
+            SymbolVendor::Yahoo => ".MC",
+            SymbolVendor::Reuters => ".MC",
+        }
+    }
+}
+
+/// Converter between BME tickers and vendor symbols with support for exceptions.
+///
+/// # Description
+///
+/// By default, a vendor symbol is built appending the vendor suffix to the
+/// ticker. Companies that do not follow that rule are registered using
+/// [SymbolConverter::with_override], and the override is applied in both
+/// directions of the conversion.
+#[derive(Debug, Clone, Default)]
+pub struct SymbolConverter {
+    to_vendor: HashMap<SymbolVendor, HashMap<Ticker, String>>,
+    from_vendor: HashMap<SymbolVendor, HashMap<String, Ticker>>,
+}
+
+impl SymbolConverter {
+    /// Build a converter that only applies the suffix rules.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register the symbol used by `vendor` for `ticker`.
+    ///
+    /// # Description
+    ///
+    /// A previous override for `ticker`, or for `symbol`, is replaced, so no two
+    /// overrides share a ticker or a symbol.
+    ///
+    /// Overrides are not checked against the suffix rules: if `symbol` is the
+    /// default symbol of another ticker, e.g. `BBVA.MC` registered for _SAN_,
+    /// that symbol is parsed as `ticker` from then on, while the other ticker
+    /// keeps converting to it. Such overrides break the round trip of the
+    /// other ticker and should be avoided.
+    pub fn with_override(mut self, vendor: SymbolVendor, ticker: Ticker, symbol: &str) -> Self {
+        let to_vendor = self.to_vendor.entry(vendor).or_default();
+        let from_vendor = self.from_vendor.entry(vendor).or_default();
+
+        if let Some(old_symbol) = to_vendor.remove(&ticker) {
+            from_vendor.remove(&old_symbol);
+        }
+        if let Some(old_ticker) = from_vendor.remove(symbol) {
+            to_vendor.remove(&old_ticker);
+        }
+
+        from_vendor.insert(symbol.to_string(), ticker.clone());
+        to_vendor.insert(ticker, symbol.to_string());
+        self
+    }
+
+    /// Get the symbol used by `vendor` for `ticker`.
+    pub fn to_vendor(&self, vendor: SymbolVendor, ticker: &Ticker) -> String {
+        match self.to_vendor.get(&vendor).and_then(|map| map.get(ticker)) {
+            Some(symbol) => symbol.clone(),
+            None => format!("{ticker}{}", vendor.suffix()),
+        }
+    }
+
+    /// Get the BME ticker of a symbol used by `vendor`.
+    ///
+    /// # Description
+    ///
+    /// Overrides are checked first. Otherwise, the vendor suffix is stripped
+    /// from `symbol` and the remainder is validated as a [Ticker].
+    ///
+    /// ## Returns
+    ///
+    /// An error is returned when `symbol` lacks the vendor suffix or the
+    /// remainder is not a valid ticker.
+    pub fn from_vendor(&self, vendor: SymbolVendor, symbol: &str) -> Result<Ticker, IdError> {
+        if let Some(ticker) = self
+            .from_vendor
+            .get(&vendor)
+            .and_then(|map| map.get(symbol))
+        {
+            return Ok(ticker.clone());
+        }
+
+        match symbol.strip_suffix(vendor.suffix()) {
+            Some(ticker) => Ticker::new(ticker),
+            None => Err(IdError::InvalidTicker(symbol.to_string())),
+        }
+    }
+}
+
+/// Get the Yahoo Finance symbol of `ticker` using the default rules.
+pub fn to_yahoo(ticker: &Ticker) -> String {
+    SymbolConverter::new().to_vendor(SymbolVendor::Yahoo, ticker)
+}
+
+/// Get the Reuters Instrument Code of `ticker` using the default rules.
+pub fn to_ric(ticker: &Ticker) -> String {
+    SymbolConverter::new().to_vendor(SymbolVendor::Reuters, ticker)
+}
+
+/// Parse a Yahoo Finance symbol into a BME ticker using the default rules.
+pub fn from_yahoo(symbol: &str) -> Result<Ticker, IdError> {
+    SymbolConverter::new().from_vendor(SymbolVendor::Yahoo, symbol)
+}
+
+/// Parse a Reuters Instrument Code into a BME ticker using the default rules.
+pub fn from_ric(symbol: &str) -> Result<Ticker, IdError> {
+    SymbolConverter::new().from_vendor(SymbolVendor::Reuters, symbol)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+
+    fn ticker(t: &str) -> Ticker {
+        Ticker::new(t).unwrap()
+    }
+
+    /// Test case for the default suffix rules in both directions.
+    #[rstest]
+    #[case("SAN")]
+    #[case("BBVA")]
+    #[case("A3M")]
+    fn default_round_trip(#[case] t: &str) {
+        let symbol = format!("{t}.MC");
+
+        assert_eq!(to_yahoo(&ticker(t)), symbol);
+        assert_eq!(to_ric(&ticker(t)), symbol);
+        assert_eq!(from_yahoo(&symbol), Ok(ticker(t)));
+        assert_eq!(from_ric(&symbol), Ok(ticker(t)));
+    }
+
+    /// Test case for symbols that can't be parsed with the default rules.
+    #[rstest]
+    #[case::no_suffix("SAN")]
+    #[case::other_exchange("SAN.L")]
+    #[case::lowercase("san.MC")]
+    #[case::empty_ticker(".MC")]
+    fn invalid_symbol(#[case] symbol: &str) {
+        assert!(from_yahoo(symbol).is_err());
+    }
+
+    /// Test case for an override applied in both directions.
+    #[rstest]
+    fn override_round_trip() {
+        let converter =
+            SymbolConverter::new().with_override(SymbolVendor::Reuters, ticker("MTS"), "MTS.MC1");
+
+        assert_eq!(
+            converter.to_vendor(SymbolVendor::Reuters, &ticker("MTS")),
+            "MTS.MC1"
+        );
+        assert_eq!(
+            converter.from_vendor(SymbolVendor::Reuters, "MTS.MC1"),
+            Ok(ticker("MTS"))
+        );
+        // Overrides are per vendor.
+        assert_eq!(
+            converter.to_vendor(SymbolVendor::Yahoo, &ticker("MTS")),
+            "MTS.MC"
+        );
+    }
+
+    /// Test case for a ticker whose override is registered twice.
+    #[rstest]
+    fn override_replaces_previous_symbol() {
+        let converter = SymbolConverter::new()
+            .with_override(SymbolVendor::Yahoo, ticker("SAN"), "A.MC")
+            .with_override(SymbolVendor::Yahoo, ticker("SAN"), "B.MC");
+
+        assert_eq!(
+            converter.to_vendor(SymbolVendor::Yahoo, &ticker("SAN")),
+            "B.MC"
+        );
+        assert_eq!(
+            converter.from_vendor(SymbolVendor::Yahoo, "B.MC"),
+            Ok(ticker("SAN"))
+        );
+        // The stale symbol falls back to the suffix rules.
+        assert_eq!(
+            converter.from_vendor(SymbolVendor::Yahoo, "A.MC"),
+            Ok(ticker("A"))
+        );
+    }
+
+    /// Test case for a symbol whose override is moved to another ticker.
+    #[rstest]
+    fn override_replaces_previous_ticker() {
+        let converter = SymbolConverter::new()
+            .with_override(SymbolVendor::Yahoo, ticker("SAN"), "X.MC")
+            .with_override(SymbolVendor::Yahoo, ticker("BBVA"), "X.MC");
+
+        assert_eq!(
+            converter.from_vendor(SymbolVendor::Yahoo, "X.MC"),
+            Ok(ticker("BBVA"))
+        );
+        assert_eq!(
+            converter.to_vendor(SymbolVendor::Yahoo, &ticker("SAN")),
+            "SAN.MC"
+        );
+    }
+
+    /// Test case for an override that takes the default symbol of another ticker.
+    #[rstest]
+    fn override_shadows_default_symbol() {
+        let converter =
+            SymbolConverter::new().with_override(SymbolVendor::Yahoo, ticker("SAN"), "BBVA.MC");
+
+        assert_eq!(
+            converter.from_vendor(SymbolVendor::Yahoo, "BBVA.MC"),
+            Ok(ticker("SAN"))
+        );
+        // BBVA still converts to its default symbol, which now parses as SAN.
+        assert_eq!(
+            converter.to_vendor(SymbolVendor::Yahoo, &ticker("BBVA")),
+            "BBVA.MC"
+        );
+    }
+}
@@ -0,0 +1,345 @@
+// Copyright 2024 Felipe Torres González
+
+//! Intraday volume profile of a trading session.
+//!
+//! Volume is not traded evenly along the session: it concentrates near the
+//! open and, above all, near the close, which draws the typical U-shaped
+//! curve. Execution-scheduling tools use such a curve to split an order
+//! following the expected volume.
+
+use crate::error::IbexError;
+use chrono::{Duration, NaiveTime};
+use toml::Table;
+
+/// A slice of the session with the share of the daily volume traded in it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct VolumeBucket {
+    /// Start time of the bucket (market local time).
+    pub start: NaiveTime,
+    /// End time of the bucket (market local time).
+    pub end: NaiveTime,
+    /// Fraction of the daily volume traded in the bucket.
+    pub fraction: f64,
+}
+
+/// Typical intraday volume profile of a market.
+///
+/// # Description
+///
+/// The curve is made of contiguous buckets that span the whole session. The
+/// weights given to each bucket are normalized, so the fractions of all the
+/// buckets add up to 1.
+///
+/// Times are expressed in the local time of the market, i.e. Madrid time for
+/// the Ibex indexes.
+#[derive(Debug, Clone, PartialEq)]
+pub struct VolumeCurve {
+    buckets: Vec<VolumeBucket>,
+}
+
+impl VolumeCurve {
+    /// Build a curve from a list of `(start, end, weight)` buckets.
+    ///
+    /// # Description
+    ///
+    /// Buckets must be sorted, contiguous (each one starts when the previous
+    /// one ends), non-empty in time and have non-negative weights whose sum is
+    /// positive. Weights are normalized to fractions of the daily volume.
+    pub fn new(buckets: &[(NaiveTime, NaiveTime, f64)]) -> Result<Self, IbexError> {
+        let invalid = |msg: &str| Err(IbexError::InvalidVolumeCurve(msg.to_string()));
+
+        if buckets.is_empty() {
+            return invalid("no buckets were given");
+        }
+        if buckets
+            .iter()
+            .any(|(start, end, weight)| start >= end || !weight.is_finite() || *weight < 0.0)
+        {
+            return invalid("buckets must have start < end and non-negative weights");
+        }
+        if buckets.windows(2).any(|w| match w {
+            [(_, end, _), (start, _, _)] => end != start,
+            _ => false,
+        }) {
+            return invalid("buckets must be contiguous");
+        }
+
+        let total: f64 = buckets.iter().map(|(_, _, weight)| weight).sum();
+        if total <= 0.0 {
+            return invalid("the sum of the weights must be positive");
+        }
+
+        Ok(VolumeCurve::normalized(buckets, total))
+    }
+
+    /// Build a curve from already validated buckets whose weights add up to `total`.
+    fn normalized(buckets: &[(NaiveTime, NaiveTime, f64)], total: f64) -> Self {
+        VolumeCurve {
+            buckets: buckets
+                .iter()
+                .map(|&(start, end, weight)| VolumeBucket {
+                    start,
+                    end,
+                    fraction: weight / total,
+                })
+                .collect(),
+        }
+    }
+
+    /// Build a curve from a TOML description.
+    ///
+    /// # Description
+    ///
+    /// The input shall contain an array of `bucket` tables, with times in
+    /// `HH:MM` format:
+    ///
+    /// ```toml
+    /// [[bucket]]
+    /// start = "09:00"
+    /// end = "09:30"
+    /// weight = 10.0
+    /// ```
+    pub fn from_toml_str(data: &str) -> Result<Self, IbexError> {
+        let table = data.parse::<Table>()?;
+        let invalid = |msg: String| IbexError::InvalidVolumeCurve(msg);
+
+        let entries = table
+            .get("bucket")
+            .and_then(|b| b.as_array())
+            .ok_or_else(|| invalid("missing array of bucket tables".to_string()))?;
+
+        let mut buckets = Vec::with_capacity(entries.len());
+        for (i, entry) in entries.iter().enumerate() {
+            let time = |key: &str| {
+                entry
+                    .get(key)
+                    .and_then(|v| v.as_str())
+                    .and_then(|v| NaiveTime::parse_from_str(v, "%H:%M").ok())
+                    .ok_or_else(|| invalid(format!("bucket {i} lacks a valid {key:?} time")))
+            };
+            let weight = entry
+                .get("weight")
+                .and_then(|v| v.as_float().or_else(|| v.as_integer().map(|w| w as f64)))
+                .ok_or_else(|| invalid(format!("bucket {i} lacks a valid \"weight\"")))?;
+
+            buckets.push((time("start")?, time("end")?, weight));
+        }
+
+        VolumeCurve::new(&buckets)
+    }
+
+    /// Get the buckets of the curve.
+    pub fn buckets(&self) -> &[VolumeBucket] {
+        &self.buckets
+    }
+
+    /// Fraction of the daily volume expected to be traded by `time`.
+    ///
+    /// # Description
+    ///
+    /// The volume of each bucket is assumed to be traded evenly within it, so
+    /// the result grows linearly inside a bucket. Times before the first
+    /// bucket return 0, and times after the last one return 1.
+    pub fn expected_volume_fraction(&self, time: NaiveTime) -> f64 {
+        let mut traded = 0.0;
+
+        for bucket in &self.buckets {
+            if time >= bucket.end {
+                traded += bucket.fraction;
+            } else if time > bucket.start {
+                let elapsed = (time - bucket.start).num_milliseconds() as f64;
+                let length = (bucket.end - bucket.start).num_milliseconds() as f64;
+                traded += bucket.fraction * elapsed / length;
+            }
+        }
+
+        traded.min(1.0)
+    }
+}
+
+impl Default for VolumeCurve {
+    /// U-shaped curve for the BME session.
+    ///
+    /// # Description
+    ///
+    /// Continuous trading from 09:00 to 17:30 is split in half-hour buckets,
+    /// followed by the closing auction from 17:30 to 17:35, which usually
+    /// concentrates the largest share of the daily volume.
+    fn default() -> Self {
+        VolumeCurve::normalized(&bme_buckets(), BME_WEIGHTS.iter().sum())
+    }
+}
+
+/// Start of continuous trading in the BME session.
+const BME_OPEN: NaiveTime = NaiveTime::from_hms_opt(9, 0, 0).expect("09:00 is a valid time");
+
+/// Weights of the half-hour buckets from 09:00 to 17:30, followed by the
+/// weight of the closing auction.
+const BME_WEIGHTS: [f64; 18] = [
+    8.0, 6.0, 5.0, 4.5, 4.0, 3.5, 3.5, 3.5, 3.5, 3.5, 4.0, 4.5, 5.0, 5.5, 6.0, 7.0, 8.5, 15.0,
+];
+
+/// Buckets of the default BME curve, with the weights not yet normalized.
+fn bme_buckets() -> Vec<(NaiveTime, NaiveTime, f64)> {
+    let mut start = BME_OPEN;
+
+    BME_WEIGHTS
+        .iter()
+        .enumerate()
+        .map(|(i, &weight)| {
+            let length = if i + 1 == BME_WEIGHTS.len() { 5 } else { 30 };
+            let end = start + Duration::minutes(length);
+            let bucket = (start, end, weight);
+            start = end;
+            bucket
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+
+    fn at(h: u32, m: u32) -> NaiveTime {
+        NaiveTime::from_hms_opt(h, m, 0).unwrap()
+    }
+
+    /// Test case for the buckets of the default curve, which must pass validation.
+    #[rstest]
+    fn default_curve_is_valid() {
+        let curve = VolumeCurve::default();
+
+        assert_eq!(VolumeCurve::new(&bme_buckets()).unwrap(), curve);
+        assert_eq!(curve.buckets().first().map(|b| b.start), Some(at(9, 0)));
+        assert_eq!(curve.buckets().last().map(|b| b.end), Some(at(17, 35)));
+    }
+
+    /// Test case for the fractions of the default curve.
+    #[rstest]
+    fn default_fractions_sum_to_one() {
+        let curve = VolumeCurve::default();
+        let total: f64 = curve.buckets().iter().map(|b| b.fraction).sum();
+
+        assert_eq!(curve.buckets().len(), 18);
+        assert!((total - 1.0).abs() < 1e-12);
+    }
+
+    /// Test case for the expected volume fraction outside the default session.
+    #[rstest]
+    #[case::before_open(at(8, 59), 0.0)]
+    #[case::at_open(at(9, 0), 0.0)]
+    #[case::at_close(at(17, 35), 1.0)]
+    #[case::after_close(at(17, 36), 1.0)]
+    fn default_fraction_bounds(#[case] time: NaiveTime, #[case] expected: f64) {
+        assert_eq!(
+            VolumeCurve::default().expected_volume_fraction(time),
+            expected
+        );
+    }
+
+    /// Test case for the linear growth of the expected volume within a bucket.
+    #[rstest]
+    #[case::first_quarter(at(9, 15), 0.125)]
+    #[case::bucket_edge(at(9, 30), 0.25)]
+    #[case::second_middle(at(9, 45), 0.625)]
+    #[case::end(at(10, 0), 1.0)]
+    fn linear_within_bucket(#[case] time: NaiveTime, #[case] expected: f64) {
+        let curve =
+            VolumeCurve::new(&[(at(9, 0), at(9, 30), 1.0), (at(9, 30), at(10, 0), 3.0)]).unwrap();
+
+        assert!((curve.expected_volume_fraction(time) - expected).abs() < 1e-12);
+    }
+
+    /// Test case for bucket lists rejected by [VolumeCurve::new].
+    #[rstest]
+    #[case::empty(&[])]
+    #[case::gap(&[(at(9, 0), at(9, 30), 1.0), (at(10, 0), at(10, 30), 1.0)])]
+    #[case::overlap(&[(at(9, 0), at(9, 30), 1.0), (at(9, 15), at(10, 0), 1.0)])]
+    #[case::empty_bucket(&[(at(9, 0), at(9, 0), 1.0)])]
+    #[case::negative_weight(&[(at(9, 0), at(9, 30), 2.0), (at(9, 30), at(10, 0), -1.0)])]
+    #[case::nan_weight(&[(at(9, 0), at(9, 30), f64::NAN)])]
+    #[case::zero_total(&[(at(9, 0), at(9, 30), 0.0), (at(9, 30), at(10, 0), 0.0)])]
+    fn invalid_buckets(#[case] buckets: &[(NaiveTime, NaiveTime, f64)]) {
+        assert!(matches!(
+            VolumeCurve::new(buckets),
+            Err(IbexError::InvalidVolumeCurve(_))
+        ));
+    }
+
+    /// Test case for a valid TOML description.
+    #[rstest]
+    fn toml_curve() {
+        let data = r#"
+            [[bucket]]
+            start = "09:00"
+            end = "09:30"
+            weight = 1
+
+            [[bucket]]
+            start = "09:30"
+            end = "10:00"
+            weight = 3.0
+        "#;
+        let curve = VolumeCurve::from_toml_str(data).unwrap();
+        let fractions: Vec<f64> = curve.buckets().iter().map(|b| b.fraction).collect();
+
+        assert_eq!(fractions, vec![0.25, 0.75]);
+    }
+
+    /// Test case for TOML descriptions rejected by [VolumeCurve::from_toml_str].
+    #[rstest]
+    #[case::missing_buckets("weight = 1.0")]
+    #[case::not_an_array(r#"bucket = "09:00""#)]
+    #[case::missing_start(
+        r#"[[bucket]]
+        end = "09:30"
+        weight = 1.0"#
+    )]
+    #[case::missing_end(
+        r#"[[bucket]]
+        start = "09:00"
+        weight = 1.0"#
+    )]
+    #[case::missing_weight(
+        r#"[[bucket]]
+        start = "09:00"
+        end = "09:30""#
+    )]
+    #[case::seconds_in_time(
+        r#"[[bucket]]
+        start = "09:00:00"
+        end = "09:30"
+        weight = 1.0"#
+    )]
+    #[case::toml_time(
+        r#"[[bucket]]
+        start = 09:00:00
+        end = "09:30"
+        weight = 1.0"#
+    )]
+    #[case::string_weight(
+        r#"[[bucket]]
+        start = "09:00"
+        end = "09:30"
+        weight = "1.0""#
+    )]
+    #[case::gap(
+        r#"[[bucket]]
+        start = "09:00"
+        end = "09:30"
+        weight = 1.0
+
+        [[bucket]]
+        start = "10:00"
+        end = "10:30"
+        weight = 1.0"#
+    )]
+    fn invalid_toml(#[case] data: &str) {
+        assert!(matches!(
+            VolumeCurve::from_toml_str(data),
+            Err(IbexError::InvalidVolumeCurve(_))
+        ));
+    }
+}